@@ -1,6 +1,8 @@
 use std::fs::{self, File};
 use std::io::{self, Read, ErrorKind};
 use std::cmp::Ordering;
+use std::fmt;
+use std::num::ParseIntError;
 use rand::Rng;
 
 // So far, all the main functions we’ve used return ().
@@ -28,7 +30,15 @@ use rand::Rng;
 
 
 
-fn main() {
+// main delegates to run() and propagates whatever it returns. Returning
+// Result<(), AppError> from main means a failing run() exits the process
+// with a nonzero status via the Termination trait, instead of us having to
+// pick an ExitCode by hand.
+fn main() -> Result<(), AppError> {
+    run()
+}
+
+fn run() -> Result<(), AppError> {
     // panic!("Goodbye, world!");
 
     // let v = vec![1, 2, 3];
@@ -92,8 +102,10 @@ fn main() {
     // println!("{:?}", username);
     // let last_char = last_char_of_first_line(&username);
     // println!("{:?}", last_char);
-    guess_a_number();
+    let config = select_difficulty()?;
+    guess_a_number(&config)?;
 
+    Ok(())
 }
 
 
@@ -129,9 +141,10 @@ fn main() {
 // }
 
 // shortest 
-fn read_username_from_file() -> Result<String, io::Error> {
+fn read_username_from_file() -> Result<String, AppError> {
     // Reading a file into a string is a fairly common operation, so the standard library provides the convenient fs::read_to_string function that opens the file, creates a new String, reads the contents of the file, puts the contents into that String, and returns it. Of course, using fs::read_to_string doesn’t give us the opportunity to explain all the error handling, so we did it the longer way first.
-    fs::read_to_string("hello.txt")
+    // The io::Error this can fail with converts into AppError via From, so ? composes here the same as everywhere else in the crate.
+    Ok(fs::read_to_string("hello.txt")?)
 }
 
 // Listing 9-11: Using the ? operator on an Option<T> value
@@ -150,7 +163,68 @@ fn last_char_of_first_line(text: &str) -> Option<char> {
 //  in those cases, you can use methods like the ok method on Result or the ok_or method on Option to do the conversion explicitly.
 
 
-fn guess_a_number() {
+// GameConfig parameterizes a round of the guessing game: the secret number's
+// range, the range Guess::new validates against (the two always agree, unlike
+// the original hardcoded 0..100 / 1..=100 mismatch), and an optional cap on
+// how many guesses the player gets before losing the round.
+pub struct GameConfig {
+    min: i32,
+    max: i32,
+    max_attempts: Option<u32>,
+}
+
+impl GameConfig {
+    pub fn easy() -> GameConfig {
+        GameConfig { min: 1, max: 50, max_attempts: Some(15) }
+    }
+
+    pub fn medium() -> GameConfig {
+        GameConfig { min: 1, max: 100, max_attempts: Some(10) }
+    }
+
+    pub fn hard() -> GameConfig {
+        GameConfig { min: 1, max: 500, max_attempts: Some(6) }
+    }
+}
+
+// Reads a difficulty choice from stdin, re-prompting on anything we don't
+// recognize, the same way guess_a_number re-prompts on a bad guess.
+fn select_difficulty() -> Result<GameConfig, AppError> {
+    println!("Choose a difficulty: (e)asy, (m)edium, or (h)ard?");
+
+    loop {
+        let mut choice = String::new();
+        let bytes_read = io::stdin().read_line(&mut choice)?;
+
+        // read_line returns Ok(0) on EOF rather than an Err, so without this
+        // check a closed stdin (Ctrl-D, `echo | prog`) would leave `choice`
+        // empty forever and spin in the `_` arm below instead of exiting.
+        if bytes_read == 0 {
+            return Err(AppError::Io(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "no input (EOF) while choosing difficulty",
+            )));
+        }
+
+        match choice.trim().to_lowercase().as_str() {
+            "e" | "easy" => return Ok(GameConfig::easy()),
+            "m" | "medium" => return Ok(GameConfig::medium()),
+            "h" | "hard" => return Ok(GameConfig::hard()),
+            _ => println!("Please type e, m, or h."),
+        }
+    }
+}
+
+// Parses and validates one line of guess input, composing the two fallible
+// steps with ? so both ParseIntError and GuessError convert into AppError
+// uniformly instead of being map_err'd by hand. The caller still decides
+// whether a given AppError is recoverable (re-prompt) or fatal.
+fn parse_guess(line: &str, config: &GameConfig) -> Result<Guess, AppError> {
+    let value: i32 = line.trim().parse()?;
+    Ok(Guess::new(value, config.min, config.max)?)
+}
+
+fn guess_a_number(config: &GameConfig) -> Result<(), AppError> {
     let small_variations = [
         "Well, butter my biscuit! That guess is smaller than a flea on a flea's back! Try again!",
         "Oh dear, that guess is tinier than a teaspoon in a sea of soup! Give it another shot!",
@@ -180,8 +254,8 @@ fn guess_a_number() {
 
 
 
-    let secret_number = rand::thread_rng().gen_range(0..100);
-    let mut attempts :i32 = 0;
+    let secret_number = rand::thread_rng().gen_range(config.min..=config.max);
+    let mut attempts: u32 = 0;
     println!("Welcome to the guessing game of epic proportions!");
     println!("Alrighty, what number are you tossing into the ring today?");
 
@@ -190,47 +264,182 @@ fn guess_a_number() {
         let random_index_large = rand::thread_rng().gen_range(0..large_variations.len());
     
         let mut guess = String::new();
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read lines");
+        let bytes_read = io::stdin().read_line(&mut guess)?;
+
+        // read_line returns Ok(0) on EOF rather than an Err, so without this
+        // check a closed stdin (Ctrl-D, `echo | prog`) would leave `guess`
+        // empty forever and spin in the parse-error arm below instead of
+        // ending the round.
+        if bytes_read == 0 {
+            println!("No more input, ending the game.");
+            return Ok(());
+        }
 
-        let guess_num = guess.trim().parse();
-        let guess = Guess::new(guess_num.expect("guess must be a number"));
+        // A non-numeric line or an out-of-range guess isn't a bug in the
+        // program, it's recoverable bad input, so we match on the AppError
+        // variant parse_guess produced instead of propagating it: on
+        // Parse/Guess we print a friendly message and continue the loop for
+        // another guess.
+        let guess = match parse_guess(&guess, config) {
+            Ok(guess) => guess,
+            Err(AppError::Parse(_)) => {
+                println!("Please type a number!");
+                continue;
+            }
+            Err(AppError::Guess(e)) => {
+                println!("{}", e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
         attempts += 1;
 
         match guess.value.cmp(&secret_number) {
             Ordering::Less => println!("{}", small_variations[random_index_small]),
             Ordering::Greater => println!("{}", large_variations[random_index_large]),
             Ordering::Equal => {
-                println!("Cue the confetti!!"); 
-                println!("The secret number is indeed {}! You guessed it right with only {} tries! ", secret_number, attempts); 
+                println!("Cue the confetti!!");
+                println!("The secret number is indeed {}! You guessed it right with only {} tries! ", secret_number, attempts);
+                break;
+            }
+        }
+
+        if let Some(max_attempts) = config.max_attempts {
+            if attempts >= max_attempts {
+                println!("You lose, the number was {}.", secret_number);
                 break;
             }
         }
     }
+
+    Ok(())
+}
+
+// GuessError is a recoverable alternative to panic!: instead of aborting the
+// whole program when a caller hands Guess::new a bad value, we hand the
+// problem back as data so the caller can decide what to do about it
+// (report it, ask again, whatever fits).
+#[derive(Debug, PartialEq, Eq)]
+pub enum GuessError {
+    TooLow(i32),
+    TooHigh(i32),
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::TooLow(value) => write!(f, "Guess value {} is too low", value),
+            GuessError::TooHigh(value) => write!(f, "Guess value {} is too high", value),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+// AppError is the crate's single error channel: io::Error, ParseIntError, and
+// GuessError all convert into it via From, so functions across the crate can
+// declare -> Result<T, AppError> and use ? uniformly instead of each caller
+// having to map_err three incompatible error types into something common.
+#[derive(Debug)]
+pub enum AppError {
+    Io(io::Error),
+    Parse(ParseIntError),
+    Guess(GuessError),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Io(e) => write!(f, "I/O error: {}", e),
+            AppError::Parse(e) => write!(f, "parse error: {}", e),
+            AppError::Guess(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io(e) => Some(e),
+            AppError::Parse(e) => Some(e),
+            AppError::Guess(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        AppError::Io(e)
+    }
+}
+
+impl From<ParseIntError> for AppError {
+    fn from(e: ParseIntError) -> Self {
+        AppError::Parse(e)
+    }
+}
+
+impl From<GuessError> for AppError {
+    fn from(e: GuessError) -> Self {
+        AppError::Guess(e)
+    }
 }
 
 // First, we define a struct named Guess that has a field named value that holds an i32. This is where the number will be stored.
+// Derives Debug/PartialEq so Result<Guess, GuessError> is comparable in tests.
+#[derive(Debug, PartialEq)]
 pub struct Guess {
     value: i32
 }
 
 impl Guess {
-    // Then we implement an associated function named new on Guess that creates instances of Guess values. The new function is defined to have one parameter named value of type i32 and to return a Guess. 
-    pub fn new(value: i32) -> Guess {
-        if value < 1 || value > 100 {
-        //If value doesn’t pass this test, we make a panic! call, which will alert the programmer who is writing the calling code that they have a bug they need to fix, because creating a Guess with a value outside this range would violate the contract that Guess::new is relying on. 
-            panic!("Guess value must be between 1 and 100, got {}", value);
+    // Then we implement an associated function named new on Guess that creates instances of Guess values. The new function takes the value to check plus the min and max of the valid range, all of type i32, and returns a Result<Guess, GuessError>.
+    // Rather than panicking on an out-of-range value, we return a Result so the
+    // caller can recover (e.g. re-prompt the user) instead of the whole program aborting.
+    // The valid range is no longer hardcoded to 1..=100: the caller passes in
+    // whatever range the current GameConfig is using, so the check always
+    // matches the range the secret number was actually drawn from.
+    pub fn new(value: i32, min: i32, max: i32) -> Result<Guess, GuessError> {
+        if value < min {
+            return Err(GuessError::TooLow(value));
+        }
+        if value > max {
+            return Err(GuessError::TooHigh(value));
         }
         // If value does pass the test, we create a new Guess with its value field set to the value parameter and return the Guess.
-        Guess { value }
+        Ok(Guess { value })
     }
-    //Next, we implement a method named value that borrows self, doesn’t have any other parameters, and returns an i32. 
+    //Next, we implement a method named value that borrows self, doesn’t have any other parameters, and returns an i32.
     //This kind of method is sometimes called a getter, because its purpose is to get some data from its fields and return it.
-    // This public method is necessary because the value field of the Guess struct is private. 
+    // This public method is necessary because the value field of the Guess struct is private.
     //It’s important that the value field be private so code using the Guess struct is not allowed to set value directly
     // code outside the module must use the Guess::new function to create an instance of Guess, thereby ensuring there’s no way for a Guess to have a value that hasn’t been checked by the conditions in the Guess::new function.
     pub fn value(&self) -> i32 {
         self.value
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_is_too_low() {
+        assert_eq!(Guess::new(0, 1, 100), Err(GuessError::TooLow(0)));
+    }
+
+    #[test]
+    fn one_is_the_lowest_valid_guess() {
+        assert_eq!(Guess::new(1, 1, 100).unwrap().value(), 1);
+    }
+
+    #[test]
+    fn one_hundred_is_the_highest_valid_guess() {
+        assert_eq!(Guess::new(100, 1, 100).unwrap().value(), 100);
+    }
+
+    #[test]
+    fn one_hundred_and_one_is_too_high() {
+        assert_eq!(Guess::new(101, 1, 100), Err(GuessError::TooHigh(101)));
+    }
 }
\ No newline at end of file